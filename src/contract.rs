@@ -1,13 +1,26 @@
 use cosmwasm_std::{
-    attr, entry_point, to_binary, BankMsg, Binary, Coin, CosmosMsg, Deps, DepsMut, Env,
-    MessageInfo, Response, StdResult, Uint128, WasmMsg, WasmQuery,
+    attr, entry_point, to_binary, Addr, Api, BankMsg, Binary, Coin, CosmosMsg, Decimal, Deps,
+    DepsMut, Env, MessageInfo, Order, Response, StdResult, Storage, Uint128, WasmMsg, WasmQuery,
 };
 
+use cw2::{get_contract_version, set_contract_version};
+use semver::Version;
+
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InfoResponse, InstantiateMsg, QueryMsg};
-use crate::state::{State, STATE};
+use crate::msg::{
+    DenomInfo, DenomsResponse, ExecuteMsg, InfoResponse, InstantiateMsg, MigrateMsg, QueryMsg,
+    SharePriceResponse,
+};
+use crate::state::{ContractStatus, State, StateV1, DENOMS, STATE, STATE_V1};
+
+use cw20::{
+    AllowanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg, MinterResponse,
+    TokenInfoResponse,
+};
 
-use cw20::{AllowanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg};
+const CONTRACT_NAME: &str = "crates.io:cw-wjuno";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+const MAX_FEE_BPS: u16 = 10_000;
 
 // Note, you can use StdResult in some functions where you do not
 // make use of the custom errors
@@ -16,18 +29,77 @@ pub fn instantiate(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
-    _msg: InstantiateMsg,
+    msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
     let state = State {
         owner: info.sender,
-        contract: "".into(),
-        native_coin: _msg.native_coin,
+        vault_mode: msg.vault_mode,
+        status: ContractStatus::Normal,
+        status_reason: None,
+        fee_bps: 0,
+        treasury: None,
     };
     STATE.save(deps.storage, &state)?;
 
     Ok(Response::default())
 }
 
+#[entry_point]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::WrongMigrationContract {
+            expected: CONTRACT_NAME.into(),
+            found: stored.contract,
+        });
+    }
+
+    let stored_version: Version = stored.version.parse()?;
+    let new_version: Version = CONTRACT_VERSION.parse()?;
+    if stored_version > new_version {
+        return Err(ContractError::CannotMigrateToLowerVersion {
+            stored: stored.version,
+            target: CONTRACT_VERSION.into(),
+        });
+    }
+
+    upgrade_state(deps.storage, deps.api)?;
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::default())
+}
+
+/// Reshapes a legacy `STATE` blob into the current shape. No-op if `STATE`
+/// is already current.
+fn upgrade_state(storage: &mut dyn Storage, api: &dyn Api) -> Result<(), ContractError> {
+    if STATE.load(storage).is_ok() {
+        return Ok(());
+    }
+
+    let old = STATE_V1.load(storage)?;
+
+    if !old.contract.is_empty() {
+        let cw20 = api.addr_validate(&old.contract)?;
+        DENOMS.save(storage, &old.native_coin, &cw20)?;
+    }
+
+    let state = State {
+        owner: old.owner,
+        vault_mode: old.vault_mode,
+        status: old.status,
+        status_reason: old.status_reason,
+        fee_bps: 0,
+        treasury: None,
+    };
+    STATE.save(storage, &state)?;
+
+    Ok(())
+}
+
 // And declare a custom Error variant for the ones where you will want to make use of it
 #[entry_point]
 pub fn execute(
@@ -37,19 +109,29 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::Deposit {} => try_deposit(deps, info),
-        ExecuteMsg::Withdraw { amount } => try_withdraw(deps, env, info, amount),
-        ExecuteMsg::SetContract { contract } => try_update_contract(deps, info, contract),
+        ExecuteMsg::Deposit {} => try_deposit(deps, env, info),
+        ExecuteMsg::Withdraw {
+            amount,
+            native_coin,
+        } => try_withdraw(deps, env, info, amount, native_coin),
+        ExecuteMsg::RegisterDenom {
+            native_coin,
+            contract,
+            decimals,
+        } => try_register_denom(deps, env, info, native_coin, contract, decimals),
         ExecuteMsg::Receive {
             0: Cw20ReceiveMsg { amount, sender, .. },
-        } => try_receive(deps, info, sender, amount),
+        } => try_receive(deps, env, info, sender, amount),
+        ExecuteMsg::SetStatus { status, reason } => try_set_status(deps, info, status, reason),
+        ExecuteMsg::SetFee { fee_bps, treasury } => try_set_fee(deps, info, fee_bps, treasury),
     }
 }
 
-pub fn try_update_contract(
+pub fn try_set_fee(
     deps: DepsMut,
     info: MessageInfo,
-    contract: String,
+    fee_bps: u16,
+    treasury: Option<String>,
 ) -> Result<Response, ContractError> {
     let state = STATE.load(deps.storage)?;
 
@@ -57,39 +139,186 @@ pub fn try_update_contract(
         return Err(ContractError::Unauthorized {});
     }
 
-    if !state.contract.is_empty() {
-        return Err(ContractError::Unauthorized {});
+    if fee_bps > MAX_FEE_BPS || (fee_bps > 0 && treasury.is_none()) {
+        return Err(ContractError::InvalidFee {});
     }
 
-    deps.api.addr_validate(&contract)?;
+    let treasury = treasury.map(|t| deps.api.addr_validate(&t)).transpose()?;
 
     STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
-        state.contract = contract;
+        state.fee_bps = fee_bps;
+        state.treasury = treasury;
         Ok(state)
     })?;
 
-    Ok(Response::default())
+    Ok(Response {
+        submessages: vec![],
+        messages: vec![],
+        attributes: vec![
+            attr("action", "set_fee"),
+            attr("fee_bps", fee_bps.to_string()),
+        ],
+        data: None,
+    })
+}
+
+pub fn try_set_status(
+    deps: DepsMut,
+    info: MessageInfo,
+    status: ContractStatus,
+    reason: Option<String>,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+        state.status = status.clone();
+        state.status_reason = reason.clone();
+        Ok(state)
+    })?;
+
+    Ok(Response {
+        submessages: vec![],
+        messages: vec![],
+        attributes: vec![
+            attr("action", "set_status"),
+            attr("status", format!("{:?}", status)),
+        ],
+        data: None,
+    })
+}
+
+pub fn try_register_denom(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    native_coin: String,
+    contract: String,
+    decimals: Option<u8>,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if DENOMS.has(deps.storage, &native_coin) {
+        return Err(ContractError::DenomAlreadyRegistered {});
+    }
+
+    let contract = deps.api.addr_validate(&contract)?;
+
+    let already_linked = DENOMS
+        .range(deps.storage, None, None, Order::Ascending)
+        .any(|item| matches!(item, Ok((_, addr)) if addr == contract));
+    if already_linked {
+        return Err(ContractError::DenomAlreadyRegistered {});
+    }
+
+    validate_token_pair(deps.as_ref(), &env, contract.as_str(), decimals)?;
+
+    DENOMS.save(deps.storage, &native_coin, &contract)?;
+
+    Ok(Response {
+        submessages: vec![],
+        messages: vec![],
+        attributes: vec![
+            attr("action", "register_denom"),
+            attr("native_coin", native_coin),
+            attr("contract", contract),
+        ],
+        data: None,
+    })
 }
 
-pub fn try_deposit(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+fn validate_token_pair(
+    deps: Deps,
+    env: &Env,
+    cw20_contract: &str,
+    expected_decimals: Option<u8>,
+) -> Result<(), ContractError> {
+    let minter_request = WasmQuery::Smart {
+        contract_addr: cw20_contract.to_owned(),
+        msg: to_binary(&Cw20QueryMsg::Minter {})?,
+    }
+    .into();
+    let minter: Option<MinterResponse> = deps.querier.query(&minter_request)?;
+
+    let is_minter = minter
+        .map(|m| m.minter == env.contract.address)
+        .unwrap_or(false);
+    if !is_minter {
+        return Err(ContractError::InvalidTokenPair {});
+    }
+
+    if let Some(expected_decimals) = expected_decimals {
+        let info_request = WasmQuery::Smart {
+            contract_addr: cw20_contract.to_owned(),
+            msg: to_binary(&Cw20QueryMsg::TokenInfo {})?,
+        }
+        .into();
+        let token_info: TokenInfoResponse = deps.querier.query(&info_request)?;
+
+        if token_info.decimals != expected_decimals {
+            return Err(ContractError::InvalidTokenPair {});
+        }
+    }
+
+    Ok(())
+}
+
+fn require_denom(deps: Deps, native_coin: &str) -> Result<Addr, ContractError> {
+    DENOMS
+        .may_load(deps.storage, native_coin)?
+        .ok_or(ContractError::DenomNotRegistered {})
+}
+
+pub fn try_deposit(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
     let state = STATE.load(deps.storage)?;
 
-    if info.funds.iter().any(|x| x.denom.ne(&state.native_coin)) {
+    if matches!(
+        state.status,
+        ContractStatus::StopDeposits | ContractStatus::StopAll
+    ) {
+        return Err(ContractError::ContractStopped {});
+    }
+
+    if info.funds.len() != 1 {
         return Err(ContractError::Unauthorized {});
     }
+    let fund = &info.funds[0];
+    let native_coin = fund.denom.clone();
+    let amount_in = fund.amount;
+
+    let cw20_contract = require_denom(deps.as_ref(), &native_coin)?;
+
+    let amount_to = if state.vault_mode {
+        let total_supply = query_total_supply(deps.as_ref(), cw20_contract.as_str())?;
+        let balance_before = deps
+            .querier
+            .query_balance(&env.contract.address, &native_coin)?
+            .amount
+            - amount_in;
+
+        if total_supply.is_zero() || balance_before.is_zero() {
+            amount_in
+        } else {
+            amount_in.multiply_ratio(total_supply, balance_before)
+        }
+    } else {
+        amount_in
+    };
 
-    let amount_to = info
-        .funds
-        .iter()
-        .map(|x| x.amount)
-        .fold(0u8.into(), |acc, amount| acc + amount);
     let mint = Cw20ExecuteMsg::Mint {
         recipient: info.sender.clone().into(),
         amount: amount_to,
     };
 
     let message = WasmMsg::Execute {
-        contract_addr: state.contract,
+        contract_addr: cw20_contract.into(),
         msg: to_binary(&mint)?,
         send: vec![],
     }
@@ -97,6 +326,7 @@ pub fn try_deposit(deps: DepsMut, info: MessageInfo) -> Result<Response, Contrac
 
     let attributes = vec![
         attr("action", "deposit"),
+        attr("native_coin", native_coin),
         attr("amount", amount_to),
         attr("sender", info.sender),
     ];
@@ -113,16 +343,24 @@ pub fn try_withdraw(
     env: Env,
     info: MessageInfo,
     amount: Uint128,
+    native_coin: String,
 ) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+
+    if state.status == ContractStatus::StopAll {
+        return Err(ContractError::ContractStopped {});
+    }
+
+    let cw20_contract = require_denom(deps.as_ref(), &native_coin)?;
+
     // check balance
     let allowance = Cw20QueryMsg::Allowance {
         owner: info.sender.clone().into(),
         spender: env.contract.address.clone().into(),
     };
 
-    let state = STATE.load(deps.storage)?;
     let request = WasmQuery::Smart {
-        contract_addr: state.contract.to_owned(),
+        contract_addr: cw20_contract.to_string(),
         msg: to_binary(&allowance)?,
     }
     .into();
@@ -132,16 +370,32 @@ pub fn try_withdraw(
         return Err(ContractError::Unauthorized {});
     }
 
+    let assets_out = if state.vault_mode {
+        let total_supply = query_total_supply(deps.as_ref(), cw20_contract.as_str())?;
+        if total_supply.is_zero() {
+            return Err(ContractError::NoOutstandingShares {});
+        }
+
+        let contract_balance = deps
+            .querier
+            .query_balance(&env.contract.address, &native_coin)?
+            .amount;
+
+        amount.multiply_ratio(contract_balance, total_supply)
+    } else {
+        amount
+    };
+
     // receive cw20 tokens
-    let burn = Cw20ExecuteMsg::TransferFrom {
+    let transfer_from = Cw20ExecuteMsg::TransferFrom {
         owner: info.sender.clone().into(),
         recipient: env.contract.address.into(),
         amount,
     };
 
     let message = WasmMsg::Execute {
-        contract_addr: state.contract.to_owned(),
-        msg: to_binary(&burn)?,
+        contract_addr: cw20_contract.to_string(),
+        msg: to_binary(&transfer_from)?,
         send: vec![],
     }
     .into();
@@ -150,64 +404,129 @@ pub fn try_withdraw(
     let burn = Cw20ExecuteMsg::Burn { amount };
 
     let burn_msg = WasmMsg::Execute {
-        contract_addr: state.contract,
+        contract_addr: cw20_contract.into(),
         msg: to_binary(&burn)?,
         send: vec![],
     }
     .into();
 
+    let (user_amount, fee_amount) = split_fee(&state, assets_out);
+
     // return funds
-    let bank_send = CosmosMsg::Bank(BankMsg::Send {
+    let mut messages = vec![message, burn_msg];
+    messages.push(CosmosMsg::Bank(BankMsg::Send {
         to_address: info.sender.clone().into(),
-        amount: vec![Coin::new(amount.into(), state.native_coin)],
-    });
+        amount: vec![Coin::new(user_amount.into(), native_coin.clone())],
+    }));
+    if let Some(fee_send) = fee_bank_msg(&state, &native_coin, fee_amount) {
+        messages.push(fee_send);
+    }
 
     Ok(Response {
         submessages: vec![],
-        messages: vec![message, burn_msg, bank_send],
+        messages,
         attributes: vec![
             attr("action", "withdraw"),
+            attr("native_coin", native_coin),
             attr("amount", amount),
+            attr("assets_out", user_amount),
+            attr("fee_amount", fee_amount),
             attr("sender", info.sender),
         ],
         data: None,
     })
 }
 
+fn split_fee(state: &State, assets_out: Uint128) -> (Uint128, Uint128) {
+    if state.fee_bps == 0 || state.treasury.is_none() {
+        return (assets_out, Uint128::zero());
+    }
+
+    let fee_amount = assets_out.multiply_ratio(state.fee_bps as u128, MAX_FEE_BPS as u128);
+    (assets_out - fee_amount, fee_amount)
+}
+
+fn fee_bank_msg(state: &State, native_coin: &str, fee_amount: Uint128) -> Option<CosmosMsg> {
+    if fee_amount.is_zero() {
+        return None;
+    }
+
+    state.treasury.as_ref().map(|treasury| {
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: treasury.to_string(),
+            amount: vec![Coin::new(fee_amount.into(), native_coin)],
+        })
+    })
+}
+
 pub fn try_receive(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     sender: String,
     amount: Uint128,
 ) -> Result<Response, ContractError> {
-    // validate owner contract
     let state = STATE.load(deps.storage)?;
-    if info.sender != state.contract {
-        return Err(ContractError::Unauthorized {});
+
+    if state.status == ContractStatus::StopAll {
+        return Err(ContractError::ContractStopped {});
     }
 
+    // find which native denom this cw20 wraps
+    let native_coin = DENOMS
+        .range(deps.storage, None, None, Order::Ascending)
+        .find_map(|item| {
+            let (denom, contract) = item.ok()?;
+            (contract == info.sender).then(|| denom)
+        })
+        .ok_or(ContractError::Unauthorized {})?;
+
+    let assets_out = if state.vault_mode {
+        let total_supply = query_total_supply(deps.as_ref(), info.sender.as_str())?;
+        if total_supply.is_zero() {
+            return Err(ContractError::NoOutstandingShares {});
+        }
+
+        let contract_balance = deps
+            .querier
+            .query_balance(&env.contract.address, &native_coin)?
+            .amount;
+
+        amount.multiply_ratio(contract_balance, total_supply)
+    } else {
+        amount
+    };
+
     // burn coins
     let burn = Cw20ExecuteMsg::Burn { amount };
 
     let burn_msg = WasmMsg::Execute {
-        contract_addr: state.contract,
+        contract_addr: info.sender.into(),
         msg: to_binary(&burn)?,
         send: vec![],
     }
     .into();
 
+    let (user_amount, fee_amount) = split_fee(&state, assets_out);
+
     // withdraw coins
-    let bank_send = CosmosMsg::Bank(BankMsg::Send {
+    let mut messages = vec![burn_msg];
+    messages.push(CosmosMsg::Bank(BankMsg::Send {
         to_address: sender.to_owned(),
-        amount: vec![Coin::new(amount.into(), state.native_coin)],
-    });
+        amount: vec![Coin::new(user_amount.into(), native_coin.clone())],
+    }));
+    if let Some(fee_send) = fee_bank_msg(&state, &native_coin, fee_amount) {
+        messages.push(fee_send);
+    }
 
     Ok(Response {
         submessages: vec![],
-        messages: vec![burn_msg, bank_send],
+        messages,
         attributes: vec![
             attr("action", "receive_to_withdraw"),
+            attr("native_coin", native_coin),
             attr("amount", amount),
+            attr("fee_amount", fee_amount),
             attr("sender", sender),
         ],
         data: None,
@@ -215,35 +534,93 @@ pub fn try_receive(
 }
 
 #[entry_point]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Info {} => to_binary(&query_ctr_info(deps)?),
+        QueryMsg::Denoms {} => to_binary(&query_denoms(deps)?),
+        QueryMsg::SharePrice { native_coin } => {
+            to_binary(&query_share_price(deps, env, native_coin)?)
+        }
     }
 }
 
 pub fn query_ctr_info(deps: Deps) -> StdResult<InfoResponse> {
     let info = STATE.load(deps.storage)?;
     let res = InfoResponse {
-        cw20_contract: info.contract,
-        native_coin: info.native_coin,
+        vault_mode: info.vault_mode,
+        status: info.status,
+        status_reason: info.status_reason,
+        fee_bps: info.fee_bps,
+        treasury: info.treasury.map(|t| t.into()),
     };
     Ok(res)
 }
 
+pub fn query_denoms(deps: Deps) -> StdResult<DenomsResponse> {
+    let denoms = DENOMS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (native_coin, contract) = item?;
+            Ok(DenomInfo {
+                native_coin,
+                contract: contract.into(),
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(DenomsResponse { denoms })
+}
+
+pub fn query_share_price(
+    deps: Deps,
+    env: Env,
+    native_coin: String,
+) -> StdResult<SharePriceResponse> {
+    let cw20_contract = DENOMS.load(deps.storage, &native_coin)?;
+    let balance = deps
+        .querier
+        .query_balance(&env.contract.address, &native_coin)?
+        .amount;
+    let total_supply = query_total_supply(deps, cw20_contract.as_str())?;
+
+    let price = if total_supply.is_zero() {
+        Decimal::zero()
+    } else {
+        Decimal::from_ratio(balance, total_supply)
+    };
+
+    Ok(SharePriceResponse {
+        native_coin,
+        balance,
+        total_supply,
+        price,
+    })
+}
+
+fn query_total_supply(deps: Deps, cw20_contract: &str) -> StdResult<Uint128> {
+    let request = WasmQuery::Smart {
+        contract_addr: cw20_contract.to_owned(),
+        msg: to_binary(&Cw20QueryMsg::TokenInfo {})?,
+    }
+    .into();
+    let res: TokenInfoResponse = deps.querier.query(&request)?;
+    Ok(res.total_supply)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::mock::mock_dependencies_allowance;
+    use crate::mock::{
+        mock_dependencies_allowance, mock_dependencies_registrable, mock_dependencies_vault,
+    };
     use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::{coins, from_binary};
+    use cosmwasm_std::{coin, coins, from_binary};
 
     #[test]
     fn proper_initialization() {
         let mut deps = mock_dependencies(&[]);
 
-        let msg = InstantiateMsg {
-            native_coin: "inca".into(),
-        };
+        let msg = InstantiateMsg { vault_mode: false };
         let info = mock_info("creator", &[]);
 
         // we can just call .unwrap() to assert this was a success
@@ -253,40 +630,49 @@ mod tests {
         // it worked, let's query the state
         let res = query(deps.as_ref(), mock_env(), QueryMsg::Info {}).unwrap();
         let value: InfoResponse = from_binary(&res).unwrap();
-        assert_eq!("inca", value.native_coin);
-        assert_eq!(true, value.cw20_contract.is_empty());
+        assert_eq!(false, value.vault_mode);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Denoms {}).unwrap();
+        let value: DenomsResponse = from_binary(&res).unwrap();
+        assert_eq!(true, value.denoms.is_empty());
     }
 
     #[test]
     fn deposit() {
-        let mut deps = mock_dependencies(&[]);
+        let mut deps = mock_dependencies_registrable(&[]);
 
-        let msg = InstantiateMsg {
-            native_coin: "juno".into(),
-        };
+        let msg = InstantiateMsg { vault_mode: false };
         let info = mock_info("creator", &[]);
 
         // we can just call .unwrap() to assert this was a success
         let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
         assert_eq!(0, res.messages.len());
 
-        // set cw20 contract
+        // register the juno denom
         let info = mock_info("creator", &[]);
         let cw20_contract: String = "juno145tr".into();
-        let res = try_update_contract(deps.as_mut(), info, cw20_contract.to_owned()).unwrap();
+        let res = try_register_denom(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            "juno".into(),
+            cw20_contract.to_owned(),
+            None,
+        )
+        .unwrap();
         assert_eq!(0, res.messages.len());
 
-        // deposit invalid coin
+        // deposit an unregistered coin
         let info = mock_info("anyone", &coins(10, "btc"));
-        let err = try_deposit(deps.as_mut(), info).unwrap_err();
+        let err = try_deposit(deps.as_mut(), mock_env(), info).unwrap_err();
         match err {
-            ContractError::Unauthorized {} => {}
+            ContractError::DenomNotRegistered {} => {}
             e => panic!("unexpected error: {:?}", e),
         }
 
         // valid coin
         let info = mock_info("creator", &coins(10, "juno"));
-        let res = try_deposit(deps.as_mut(), info).unwrap();
+        let res = try_deposit(deps.as_mut(), mock_env(), info).unwrap();
         assert_eq!(res.messages.len(), 1);
         assert_eq!(
             res.messages[0],
@@ -306,25 +692,431 @@ mod tests {
     fn withdraw() {
         let mut deps = mock_dependencies_allowance(10u8.into());
 
-        let msg = InstantiateMsg {
-            native_coin: "juno".into(),
-        };
+        let msg = InstantiateMsg { vault_mode: false };
         let info = mock_info("creator", &[]);
 
         // we can just call .unwrap() to assert this was a success
         let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
         assert_eq!(0, res.messages.len());
 
-        // set cw20 contract
+        // register the juno denom
         let info = mock_info("creator", &[]);
         let cw20_contract: String = "juno145tr".into();
-        let res = try_update_contract(deps.as_mut(), info, cw20_contract.to_owned()).unwrap();
+        let res = try_register_denom(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            "juno".into(),
+            cw20_contract,
+            None,
+        )
+        .unwrap();
         assert_eq!(0, res.messages.len());
 
         // withdraw
         let info = mock_info("creator", &[]);
         let env = mock_env();
-        let res = try_withdraw(deps.as_mut(), env, info, 4u8.into()).unwrap();
+        let res = try_withdraw(deps.as_mut(), env, info, 4u8.into(), "juno".into()).unwrap();
         assert_eq!(3, res.messages.len());
     }
+
+    #[test]
+    fn vault_deposit_mints_proportional_shares() {
+        // pool already holds 100 juno backing 50 outstanding shares, so a
+        // fresh deposit of 10 juno (already counted in the contract balance
+        // by mock_dependencies_vault) should mint 10 * 50 / 100 = 5 shares.
+        let mut deps = mock_dependencies_vault(&coins(110, "juno"), 50u8.into());
+
+        let msg = InstantiateMsg { vault_mode: true };
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("creator", &[]);
+        try_register_denom(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            "juno".into(),
+            "juno145tr".into(),
+            None,
+        )
+        .unwrap();
+
+        let info = mock_info("depositor", &[coin(10, "juno")]);
+        let res = try_deposit(deps.as_mut(), mock_env(), info).unwrap();
+        assert_eq!(
+            res.messages[0],
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "juno145tr".into(),
+                msg: to_binary(&Cw20ExecuteMsg::Mint {
+                    recipient: "depositor".into(),
+                    amount: 5u8.into(),
+                })
+                .unwrap(),
+                send: vec![]
+            })
+        );
+    }
+
+    #[test]
+    fn vault_withdraw_and_receive_reject_with_no_outstanding_shares() {
+        let mut deps = mock_dependencies_vault(&coins(100, "juno"), Uint128::zero());
+
+        let msg = InstantiateMsg { vault_mode: true };
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("creator", &[]);
+        try_register_denom(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            "juno".into(),
+            "juno145tr".into(),
+            None,
+        )
+        .unwrap();
+
+        let info = mock_info("creator", &[]);
+        let err =
+            try_withdraw(deps.as_mut(), mock_env(), info, 1u8.into(), "juno".into()).unwrap_err();
+        match err {
+            ContractError::NoOutstandingShares {} => {}
+            e => panic!("unexpected error: {:?}", e),
+        }
+
+        let info = mock_info("juno145tr", &[]);
+        let err = try_receive(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            "depositor".into(),
+            1u8.into(),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::NoOutstandingShares {} => {}
+            e => panic!("unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn share_price_reflects_accrued_balance() {
+        let mut deps = mock_dependencies_vault(&coins(150, "juno"), 100u8.into());
+
+        let msg = InstantiateMsg { vault_mode: true };
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("creator", &[]);
+        try_register_denom(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            "juno".into(),
+            "juno145tr".into(),
+            None,
+        )
+        .unwrap();
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SharePrice {
+                native_coin: "juno".into(),
+            },
+        )
+        .unwrap();
+        let value: SharePriceResponse = from_binary(&res).unwrap();
+        assert_eq!(Uint128::new(150), value.balance);
+        assert_eq!(Uint128::new(100), value.total_supply);
+        assert_eq!(Decimal::percent(150), value.price);
+    }
+
+    #[test]
+    fn set_fee_rejects_invalid_config() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg { vault_mode: false };
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("creator", &[]);
+        let err = try_set_fee(deps.as_mut(), info, 10_001, Some("treasury".into())).unwrap_err();
+        match err {
+            ContractError::InvalidFee {} => {}
+            e => panic!("unexpected error: {:?}", e),
+        }
+
+        // a nonzero fee with no treasury would be collected nowhere
+        let info = mock_info("creator", &[]);
+        let err = try_set_fee(deps.as_mut(), info, 500, None).unwrap_err();
+        match err {
+            ContractError::InvalidFee {} => {}
+            e => panic!("unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn set_status_stops_deposits() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg { vault_mode: false };
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // non-owner cannot change the status
+        let info = mock_info("anyone", &[]);
+        let err = try_set_status(
+            deps.as_mut(),
+            info,
+            ContractStatus::StopAll,
+            Some("maintenance".into()),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {:?}", e),
+        }
+
+        let info = mock_info("creator", &[]);
+        try_set_status(
+            deps.as_mut(),
+            info,
+            ContractStatus::StopDeposits,
+            Some("reviewing peg".into()),
+        )
+        .unwrap();
+
+        let info = mock_info("anyone", &coins(10, "juno"));
+        let err = try_deposit(deps.as_mut(), mock_env(), info).unwrap_err();
+        match err {
+            ContractError::ContractStopped {} => {}
+            e => panic!("unexpected error: {:?}", e),
+        }
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Info {}).unwrap();
+        let value: InfoResponse = from_binary(&res).unwrap();
+        assert_eq!(ContractStatus::StopDeposits, value.status);
+        assert_eq!(Some("reviewing peg".to_string()), value.status_reason);
+    }
+
+    #[test]
+    fn migrate_rejects_downgrade_and_wrong_contract() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg { vault_mode: false };
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // same contract, same-or-newer version: succeeds
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        let stored = get_contract_version(deps.as_ref().storage).unwrap();
+        assert_eq!(CONTRACT_VERSION, stored.version);
+
+        // simulate a stored version newer than this binary (a downgrade)
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "999.0.0").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+        match err {
+            ContractError::CannotMigrateToLowerVersion { .. } => {}
+            e => panic!("unexpected error: {:?}", e),
+        }
+
+        // simulate migrating over a differently named contract
+        set_contract_version(deps.as_mut().storage, "crates.io:cw20-base", "0.1.0").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+        match err {
+            ContractError::WrongMigrationContract { .. } => {}
+            e => panic!("unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn migrate_upgrades_legacy_state() {
+        let mut deps = mock_dependencies(&[]);
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.1.0").unwrap();
+
+        // seed a pre-fee/treasury, single-denom STATE blob as an older
+        // binary would have saved it
+        let legacy = StateV1 {
+            owner: Addr::unchecked("creator"),
+            contract: "juno145tr".into(),
+            native_coin: "juno".into(),
+            vault_mode: false,
+            status: ContractStatus::Normal,
+            status_reason: None,
+        };
+        STATE_V1.save(deps.as_mut().storage, &legacy).unwrap();
+
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+        let state = STATE.load(deps.as_ref().storage).unwrap();
+        assert_eq!(legacy.owner, state.owner);
+        assert_eq!(0, state.fee_bps);
+        assert_eq!(None, state.treasury);
+
+        let denoms = query_denoms(deps.as_ref()).unwrap();
+        assert_eq!(
+            vec![DenomInfo {
+                native_coin: "juno".into(),
+                contract: "juno145tr".into(),
+            }],
+            denoms.denoms
+        );
+    }
+
+    #[test]
+    fn withdraw_splits_protocol_fee() {
+        let mut deps = mock_dependencies_allowance(Uint128::new(1000));
+
+        let msg = InstantiateMsg { vault_mode: false };
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("creator", &[]);
+        try_register_denom(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            "juno".into(),
+            "juno145tr".into(),
+            None,
+        )
+        .unwrap();
+
+        // 5% fee routed to a treasury
+        let info = mock_info("creator", &[]);
+        try_set_fee(deps.as_mut(), info, 500, Some("treasury".into())).unwrap();
+
+        let info = mock_info("creator", &[]);
+        let res =
+            try_withdraw(deps.as_mut(), mock_env(), info, 100u8.into(), "juno".into()).unwrap();
+        assert_eq!(4, res.messages.len());
+        assert_eq!(
+            res.messages[2],
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "creator".into(),
+                amount: vec![Coin::new(95, "juno")],
+            })
+        );
+        assert_eq!(
+            res.messages[3],
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "treasury".into(),
+                amount: vec![Coin::new(5, "juno")],
+            })
+        );
+    }
+
+    #[test]
+    fn register_denom_rejects_duplicate() {
+        let mut deps = mock_dependencies_registrable(&[]);
+
+        let msg = InstantiateMsg { vault_mode: false };
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("creator", &[]);
+        try_register_denom(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            "juno".into(),
+            "juno145tr".into(),
+            None,
+        )
+        .unwrap();
+
+        let info = mock_info("creator", &[]);
+        let err = try_register_denom(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            "juno".into(),
+            "juno2".into(),
+            None,
+        )
+        .unwrap_err();
+        match err {
+            ContractError::DenomAlreadyRegistered {} => {}
+            e => panic!("unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn register_denom_rejects_reused_contract() {
+        let mut deps = mock_dependencies_registrable(&[]);
+
+        let msg = InstantiateMsg { vault_mode: false };
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("creator", &[]);
+        try_register_denom(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            "juno".into(),
+            "juno145tr".into(),
+            None,
+        )
+        .unwrap();
+
+        // same cw20 contract, different native denom
+        let info = mock_info("creator", &[]);
+        let err = try_register_denom(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            "ujuno2".into(),
+            "juno145tr".into(),
+            None,
+        )
+        .unwrap_err();
+        match err {
+            ContractError::DenomAlreadyRegistered {} => {}
+            e => panic!("unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn register_denom_rejects_wrong_minter_and_decimals() {
+        let mut deps = mock_dependencies_registrable(&[]);
+
+        let msg = InstantiateMsg { vault_mode: false };
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // cw20's minter is not this contract
+        let info = mock_info("creator", &[]);
+        let err = try_register_denom(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            "juno".into(),
+            "someone-elses-token".into(),
+            None,
+        )
+        .unwrap_err();
+        match err {
+            ContractError::InvalidTokenPair {} => {}
+            e => panic!("unexpected error: {:?}", e),
+        }
+
+        // cw20 decimals don't match what the caller expects
+        let info = mock_info("creator", &[]);
+        let err = try_register_denom(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            "juno".into(),
+            "juno145tr".into(),
+            Some(18),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::InvalidTokenPair {} => {}
+            e => panic!("unexpected error: {:?}", e),
+        }
+    }
 }
@@ -0,0 +1,44 @@
+use cosmwasm_std::StdError;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    SemVer(String),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Contract operations are currently stopped")]
+    ContractStopped {},
+
+    #[error("Cannot migrate from a different contract ({found}) to {expected}")]
+    WrongMigrationContract { expected: String, found: String },
+
+    #[error("Cannot migrate from {stored} down to {target}")]
+    CannotMigrateToLowerVersion { stored: String, target: String },
+
+    #[error("fee_bps cannot exceed 10000 (100%) and requires a treasury when nonzero")]
+    InvalidFee {},
+
+    #[error("Native denom is not registered to a cw20 contract")]
+    DenomNotRegistered {},
+
+    #[error("Native denom or cw20 contract is already registered")]
+    DenomAlreadyRegistered {},
+
+    #[error("cw20 contract is not a valid pair for this wrapper")]
+    InvalidTokenPair {},
+
+    #[error("Vault has no outstanding shares to redeem against")]
+    NoOutstandingShares {},
+}
+
+impl From<semver::Error> for ContractError {
+    fn from(err: semver::Error) -> Self {
+        ContractError::SemVer(err.to_string())
+    }
+}
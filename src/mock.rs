@@ -1,8 +1,11 @@
 #![cfg(test)]
 
-use cosmwasm_std::testing::{MockApi, MockStorage};
-use cosmwasm_std::{to_binary, OwnedDeps, Querier, QuerierResult, SystemResult, Uint128};
-use cw20::BalanceResponse;
+use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage};
+use cosmwasm_std::{
+    from_binary, to_binary, Coin, OwnedDeps, Querier, QuerierResult, QueryRequest, SystemResult,
+    Uint128, WasmQuery,
+};
+use cw20::{AllowanceResponse, BalanceResponse, Cw20QueryMsg, MinterResponse, TokenInfoResponse};
 
 pub fn mock_dependencies_cw20_balance(
     balance: Uint128,
@@ -27,3 +30,92 @@ impl Querier for BalMockQuerier {
         SystemResult::Ok(to_binary(&balance_res).into())
     }
 }
+
+/// Smart queries against `"someone-elses-token"` get back an unrelated
+/// minter, to exercise `RegisterDenom`'s rejection path; every other address
+/// reports `mock_env()`'s own contract as minter.
+pub struct WjunoMockQuerier {
+    base: MockQuerier,
+    allowance: Uint128,
+    total_supply: Uint128,
+}
+
+impl Querier for WjunoMockQuerier {
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        let request: QueryRequest<cosmwasm_std::Empty> = match from_binary(&bin_request.into()) {
+            Ok(v) => v,
+            Err(e) => {
+                return SystemResult::Err(cosmwasm_std::SystemError::InvalidRequest {
+                    error: e.to_string(),
+                    request: bin_request.into(),
+                })
+            }
+        };
+
+        if let QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) = &request {
+            if let Ok(cw20_msg) = from_binary::<Cw20QueryMsg>(msg) {
+                return SystemResult::Ok(match cw20_msg {
+                    Cw20QueryMsg::Minter {} => to_binary(&Some(MinterResponse {
+                        minter: if contract_addr == "someone-elses-token" {
+                            "someone-else".into()
+                        } else {
+                            "cosmos2contract".into()
+                        },
+                        cap: None,
+                    }))
+                    .into(),
+                    Cw20QueryMsg::TokenInfo {} => to_binary(&TokenInfoResponse {
+                        name: "wrapped".into(),
+                        symbol: "WJUNO".into(),
+                        decimals: 6,
+                        total_supply: self.total_supply,
+                    })
+                    .into(),
+                    Cw20QueryMsg::Allowance { .. } => to_binary(&AllowanceResponse {
+                        allowance: self.allowance,
+                        expires: cw20::Expiration::Never {},
+                    })
+                    .into(),
+                    _ => return self.base.raw_query(bin_request),
+                });
+            }
+        }
+
+        self.base.raw_query(bin_request)
+    }
+}
+
+pub fn mock_dependencies_wjuno(
+    contract_balance: &[Coin],
+    total_supply: Uint128,
+    allowance: Uint128,
+) -> OwnedDeps<MockStorage, MockApi, WjunoMockQuerier> {
+    OwnedDeps {
+        storage: MockStorage::default(),
+        api: MockApi::default(),
+        querier: WjunoMockQuerier {
+            base: MockQuerier::new(&[("cosmos2contract", contract_balance)]),
+            allowance,
+            total_supply,
+        },
+    }
+}
+
+pub fn mock_dependencies_registrable(
+    contract_balance: &[Coin],
+) -> OwnedDeps<MockStorage, MockApi, WjunoMockQuerier> {
+    mock_dependencies_wjuno(contract_balance, Uint128::zero(), Uint128::zero())
+}
+
+pub fn mock_dependencies_allowance(
+    allowance: Uint128,
+) -> OwnedDeps<MockStorage, MockApi, WjunoMockQuerier> {
+    mock_dependencies_wjuno(&[], Uint128::zero(), allowance)
+}
+
+pub fn mock_dependencies_vault(
+    contract_balance: &[Coin],
+    total_supply: Uint128,
+) -> OwnedDeps<MockStorage, MockApi, WjunoMockQuerier> {
+    mock_dependencies_wjuno(contract_balance, total_supply, Uint128::MAX)
+}
@@ -0,0 +1,74 @@
+use cosmwasm_std::{Decimal, Uint128};
+use cw20::Cw20ReceiveMsg;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::state::ContractStatus;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    pub vault_mode: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    Deposit {},
+    Withdraw {
+        amount: Uint128,
+        native_coin: String,
+    },
+    RegisterDenom {
+        native_coin: String,
+        contract: String,
+        decimals: Option<u8>,
+    },
+    Receive(Cw20ReceiveMsg),
+    SetStatus {
+        status: ContractStatus,
+        reason: Option<String>,
+    },
+    SetFee {
+        fee_bps: u16,
+        treasury: Option<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    Info {},
+    Denoms {},
+    SharePrice { native_coin: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InfoResponse {
+    pub vault_mode: bool,
+    pub status: ContractStatus,
+    pub status_reason: Option<String>,
+    pub fee_bps: u16,
+    pub treasury: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DenomInfo {
+    pub native_coin: String,
+    pub contract: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DenomsResponse {
+    pub denoms: Vec<DenomInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SharePriceResponse {
+    pub native_coin: String,
+    pub balance: Uint128,
+    pub total_supply: Uint128,
+    pub price: Decimal,
+}
@@ -0,0 +1,42 @@
+use cosmwasm_std::Addr;
+use cw_storage_plus::{Item, Map};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct State {
+    pub owner: Addr,
+    pub vault_mode: bool,
+    pub status: ContractStatus,
+    pub status_reason: Option<String>,
+    pub fee_bps: u16,
+    pub treasury: Option<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ContractStatus {
+    Normal,
+    StopDeposits,
+    StopAll,
+}
+
+pub const STATE: Item<State> = Item::new("state");
+
+pub const DENOMS: Map<&str, Addr> = Map::new("denoms");
+
+/// Shape of `State` as saved by binaries that predate `fee_bps`/`treasury`
+/// and `DENOMS`. Read by `migrate()` to upgrade an older deployment.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StateV1 {
+    pub owner: Addr,
+    pub contract: String,
+    pub native_coin: String,
+    pub vault_mode: bool,
+    pub status: ContractStatus,
+    pub status_reason: Option<String>,
+}
+
+/// Shares `STATE`'s storage key, so it can decode whatever shape an older
+/// binary left there.
+pub const STATE_V1: Item<StateV1> = Item::new("state");